@@ -27,6 +27,9 @@ pub enum GdqBotError {
     #[error(transparent)]
     SerenityError(Box<serenity::prelude::SerenityError>),
 
+    #[error("Twitch IRC error: {0}")]
+    IrcError(String),
+
     #[error("Stream is offline after {0} consecutive checks")]
     StreamOffline(u32),
 
@@ -0,0 +1,365 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Router,
+};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::time::Duration;
+use subtle::ConstantTimeEq;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+use twitch_api::{
+    eventsub::{
+        self,
+        stream::{StreamOfflineV1, StreamOnlineV1},
+        channel::ChannelUpdateV2,
+        Transport,
+    },
+    helix::users::get_users,
+    twitch_oauth2::AppAccessToken,
+    types, HelixClient,
+};
+
+use crate::error::GdqBotError;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Reject notifications whose timestamp is older than this to prevent replay.
+const MAX_MESSAGE_AGE: Duration = Duration::from_secs(10 * 60);
+/// Helix batches up to 100 `login` values into a single `GetUsers` call.
+const HELIX_MAX_LOGINS_PER_REQUEST: usize = 100;
+
+/// A game/title change observed through an EventSub `channel.update` notification.
+#[derive(Debug, Clone)]
+pub struct ChannelUpdateEvent {
+    pub broadcaster_login: String,
+    pub category_name: String,
+    pub stream_title: String,
+}
+
+/// The EventSub notifications we subscribe to and react to instantly.
+#[derive(Debug, Clone)]
+pub enum EventSubMessage {
+    ChannelUpdate(ChannelUpdateEvent),
+    StreamOnline { broadcaster_login: String },
+    StreamOffline { broadcaster_login: String },
+}
+
+#[derive(Clone)]
+struct EventSubState {
+    secret: String,
+    tx: mpsc::UnboundedSender<EventSubMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventSubNotification {
+    subscription: EventSubSubscription,
+    event: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventSubSubscription {
+    #[serde(rename = "type")]
+    sub_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChannelUpdateEventPayload {
+    broadcaster_user_login: String,
+    category_name: String,
+    #[serde(default)]
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamStatusEventPayload {
+    broadcaster_user_login: String,
+}
+
+/// Spawns the `/eventsub` HTTP listener and returns a receiver that yields an
+/// [`EventSubMessage`] for every verified `channel.update`, `stream.online`,
+/// and `stream.offline` notification.
+///
+/// Runs until the process exits; an error binding the listener is logged and
+/// ends the task. The caller is not told of it, so a bind failure does not by
+/// itself switch the bot back to polling.
+pub fn spawn_listener(port: u16, secret: String) -> mpsc::UnboundedReceiver<EventSubMessage> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let state = EventSubState { secret, tx };
+    let app = Router::new()
+        .route("/eventsub", post(handle_eventsub))
+        .with_state(state);
+
+    tokio::spawn(async move {
+        let addr = format!("0.0.0.0:{}", port);
+        match tokio::net::TcpListener::bind(&addr).await {
+            Ok(listener) => {
+                info!("EventSub listener bound on {}", addr);
+                if let Err(e) = axum::serve(listener, app).await {
+                    warn!("EventSub listener stopped: {}", e);
+                }
+            }
+            Err(e) => warn!("Failed to bind EventSub listener on {}: {}", addr, e),
+        }
+    });
+
+    rx
+}
+
+/// Creates `channel.update`, `stream.online`, and `stream.offline` EventSub
+/// subscriptions for every channel in `channel_names`, pointed at
+/// `callback_url` and authenticated with `secret`. Resolves logins to
+/// broadcaster user IDs first, since EventSub conditions key off user ID.
+///
+/// A failure creating one subscription (e.g. a 409 because it already exists
+/// from a prior process run) is logged and does not abort the rest of the
+/// batch. Returns whether at least one subscription was created, so the
+/// caller can tell a fully-failed attempt from a working one and fall back
+/// to polling accordingly.
+pub async fn create_subscriptions(
+    helix_client: &HelixClient<'_, reqwest::Client>,
+    token: &AppAccessToken,
+    channel_names: &[String],
+    callback_url: &str,
+    secret: &str,
+) -> Result<bool, GdqBotError> {
+    let transport = Transport::webhook(callback_url, secret.to_string());
+    let mut any_created = false;
+
+    for chunk in channel_names.chunks(HELIX_MAX_LOGINS_PER_REQUEST) {
+        let logins: Vec<&types::UserNameRef> = chunk.iter().map(|c| c.as_str().into()).collect();
+        let request = get_users::GetUsersRequest::logins(&logins);
+        let users = match helix_client.req_get(request, token).await {
+            Ok(response) => response.data,
+            Err(e) => {
+                warn!("Failed to resolve user IDs for EventSub subscriptions: {}", e);
+                continue;
+            }
+        };
+
+        for user in users {
+            let broadcaster_id = user.id.clone();
+
+            match helix_client
+                .create_eventsub_subscription(
+                    ChannelUpdateV2::broadcaster_user_id(broadcaster_id.clone()),
+                    transport.clone(),
+                    token,
+                )
+                .await
+            {
+                Ok(_) => any_created = true,
+                Err(e) => warn!("Failed to create channel.update subscription for {}: {}", user.login, e),
+            }
+            match helix_client
+                .create_eventsub_subscription(
+                    StreamOnlineV1::broadcaster_user_id(broadcaster_id.clone()),
+                    transport.clone(),
+                    token,
+                )
+                .await
+            {
+                Ok(_) => any_created = true,
+                Err(e) => warn!("Failed to create stream.online subscription for {}: {}", user.login, e),
+            }
+            match helix_client
+                .create_eventsub_subscription(
+                    StreamOfflineV1::broadcaster_user_id(broadcaster_id),
+                    transport.clone(),
+                    token,
+                )
+                .await
+            {
+                Ok(_) => any_created = true,
+                Err(e) => warn!("Failed to create stream.offline subscription for {}: {}", user.login, e),
+            }
+
+            info!("Processed EventSub subscriptions for {}", user.login);
+        }
+    }
+
+    Ok(any_created)
+}
+
+async fn handle_eventsub(
+    State(state): State<EventSubState>,
+    headers: HeaderMap,
+    body: String,
+) -> (StatusCode, String) {
+    let message_type = headers
+        .get("Twitch-Eventsub-Message-Type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    if !verify_signature(&state.secret, &headers, &body) {
+        warn!("Rejected EventSub message with invalid signature");
+        return (StatusCode::FORBIDDEN, String::new());
+    }
+
+    if is_stale(&headers) {
+        warn!("Rejected stale EventSub message");
+        return (StatusCode::FORBIDDEN, String::new());
+    }
+
+    match message_type {
+        "webhook_callback_verification" => match serde_json::from_str::<serde_json::Value>(&body) {
+            Ok(value) => {
+                let challenge = value
+                    .get("challenge")
+                    .and_then(|c| c.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                (StatusCode::OK, challenge)
+            }
+            Err(e) => {
+                warn!("Failed to parse webhook_callback_verification body: {}", e);
+                (StatusCode::BAD_REQUEST, String::new())
+            }
+        },
+        "notification" => match serde_json::from_str::<EventSubNotification>(&body) {
+            Ok(notification) => {
+                dispatch_notification(&state, &notification);
+                (StatusCode::OK, String::new())
+            }
+            Err(e) => {
+                warn!("Failed to parse EventSub notification: {}", e);
+                (StatusCode::BAD_REQUEST, String::new())
+            }
+        },
+        "revocation" => {
+            warn!("EventSub subscription revoked: {}", body);
+            (StatusCode::OK, String::new())
+        }
+        other => {
+            warn!("Ignoring unknown EventSub message type: {}", other);
+            (StatusCode::OK, String::new())
+        }
+    }
+}
+
+fn dispatch_notification(state: &EventSubState, notification: &EventSubNotification) {
+    let Some(event) = notification.event.clone() else {
+        return;
+    };
+
+    let message = match notification.subscription.sub_type.as_str() {
+        "channel.update" => match serde_json::from_value::<ChannelUpdateEventPayload>(event) {
+            Ok(payload) => Some(EventSubMessage::ChannelUpdate(ChannelUpdateEvent {
+                broadcaster_login: payload.broadcaster_user_login,
+                category_name: payload.category_name,
+                stream_title: payload.title,
+            })),
+            Err(e) => {
+                warn!("Failed to parse channel.update event: {}", e);
+                None
+            }
+        },
+        "stream.online" => match serde_json::from_value::<StreamStatusEventPayload>(event) {
+            Ok(payload) => Some(EventSubMessage::StreamOnline { broadcaster_login: payload.broadcaster_user_login }),
+            Err(e) => {
+                warn!("Failed to parse stream.online event: {}", e);
+                None
+            }
+        },
+        "stream.offline" => match serde_json::from_value::<StreamStatusEventPayload>(event) {
+            Ok(payload) => Some(EventSubMessage::StreamOffline { broadcaster_login: payload.broadcaster_user_login }),
+            Err(e) => {
+                warn!("Failed to parse stream.offline event: {}", e);
+                None
+            }
+        },
+        other => {
+            warn!("Ignoring unsubscribed EventSub notification type: {}", other);
+            None
+        }
+    };
+
+    if let Some(message) = message {
+        let _ = state.tx.send(message);
+    }
+}
+
+/// Verifies the `Twitch-Eventsub-Message-Signature` header by recomputing the
+/// HMAC-SHA256 digest over message id + timestamp + raw body.
+fn verify_signature(secret: &str, headers: &HeaderMap, body: &str) -> bool {
+    let Some(message_id) = headers.get("Twitch-Eventsub-Message-Id").and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let Some(timestamp) = headers.get("Twitch-Eventsub-Message-Timestamp").and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let Some(signature) = headers.get("Twitch-Eventsub-Message-Signature").and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+
+    let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(message_id.as_bytes());
+    mac.update(timestamp.as_bytes());
+    mac.update(body.as_bytes());
+    let expected = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+    expected.as_bytes().ct_eq(signature.as_bytes()).into()
+}
+
+fn is_stale(headers: &HeaderMap) -> bool {
+    let Some(timestamp) = headers.get("Twitch-Eventsub-Message-Timestamp").and_then(|v| v.to_str().ok()) else {
+        return true;
+    };
+    let Ok(sent_at) = chrono::DateTime::parse_from_rfc3339(timestamp) else {
+        return true;
+    };
+    let age = chrono::Utc::now().signed_duration_since(sent_at.with_timezone(&chrono::Utc));
+    age.to_std().map(|age| age > MAX_MESSAGE_AGE).unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_verify_signature_accepts_matching_hmac() {
+        let secret = "s3cr3t";
+        let message_id = "msg-1";
+        let timestamp = "2024-01-01T00:00:00Z";
+        let body = "{\"hello\":true}";
+
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(message_id.as_bytes());
+        mac.update(timestamp.as_bytes());
+        mac.update(body.as_bytes());
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Twitch-Eventsub-Message-Id", HeaderValue::from_str(message_id).unwrap());
+        headers.insert("Twitch-Eventsub-Message-Timestamp", HeaderValue::from_str(timestamp).unwrap());
+        headers.insert("Twitch-Eventsub-Message-Signature", HeaderValue::from_str(&signature).unwrap());
+
+        assert!(verify_signature(secret, &headers, body));
+    }
+
+    #[test]
+    fn test_verify_signature_rejects_wrong_secret() {
+        let message_id = "msg-1";
+        let timestamp = "2024-01-01T00:00:00Z";
+        let body = "{\"hello\":true}";
+
+        let mut mac = HmacSha256::new_from_slice(b"right-secret").unwrap();
+        mac.update(message_id.as_bytes());
+        mac.update(timestamp.as_bytes());
+        mac.update(body.as_bytes());
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Twitch-Eventsub-Message-Id", HeaderValue::from_str(message_id).unwrap());
+        headers.insert("Twitch-Eventsub-Message-Timestamp", HeaderValue::from_str(timestamp).unwrap());
+        headers.insert("Twitch-Eventsub-Message-Signature", HeaderValue::from_str(&signature).unwrap());
+
+        assert!(!verify_signature("wrong-secret", &headers, body));
+    }
+}
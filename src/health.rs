@@ -0,0 +1,89 @@
+use axum::{extract::State, routing::get, Json, Router};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+/// Live bot state exposed over `/health`, shared between the polling/event
+/// loop (writer) and the HTTP handler (reader).
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct BotStatus {
+    pub current_games: HashMap<String, String>,
+    pub last_change_unix: HashMap<String, u64>,
+    pub online: HashMap<String, bool>,
+    pub offline_counts: HashMap<String, u32>,
+    pub offline_threshold: u32,
+    pub kvstore_connected: bool,
+    pub helix_connected: bool,
+}
+
+pub type SharedStatus = Arc<RwLock<BotStatus>>;
+
+impl BotStatus {
+    pub fn shared(offline_threshold: u32) -> SharedStatus {
+        Arc::new(RwLock::new(BotStatus {
+            offline_threshold,
+            ..Default::default()
+        }))
+    }
+
+    /// Marks `channel` online without touching `last_change_unix` — use this
+    /// for a poll/notification that confirms the channel is still live but
+    /// didn't change game.
+    pub fn mark_online(&mut self, channel: &str, game: &str) {
+        self.current_games.insert(channel.to_string(), game.to_string());
+        self.online.insert(channel.to_string(), true);
+        self.offline_counts.insert(channel.to_string(), 0);
+    }
+
+    /// Records that `channel`'s game actually changed to `game`, stamping
+    /// `last_change_unix` with the current unix time.
+    pub fn record_game_change(&mut self, channel: &str, game: &str) {
+        self.mark_online(channel, game);
+        self.last_change_unix.insert(channel.to_string(), now_unix());
+    }
+
+    pub fn record_offline(&mut self, channel: &str, offline_count: u32) {
+        self.online.insert(channel.to_string(), false);
+        self.offline_counts.insert(channel.to_string(), offline_count);
+    }
+
+    pub fn drop_channel(&mut self, channel: &str) {
+        self.current_games.remove(channel);
+        self.online.remove(channel);
+        self.offline_counts.remove(channel);
+        self.last_change_unix.remove(channel);
+    }
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Spawns the `/health` status endpoint. Meant to run concurrently with the
+/// bot's polling loop for the lifetime of the process.
+pub fn spawn_server(port: u16, status: SharedStatus) {
+    let app = Router::new().route("/health", get(handle_health)).with_state(status);
+
+    tokio::spawn(async move {
+        let addr = format!("0.0.0.0:{}", port);
+        match tokio::net::TcpListener::bind(&addr).await {
+            Ok(listener) => {
+                info!("Health endpoint listening on {}", addr);
+                if let Err(e) = axum::serve(listener, app).await {
+                    warn!("Health endpoint stopped: {}", e);
+                }
+            }
+            Err(e) => warn!("Failed to bind health endpoint on {}: {}", addr, e),
+        }
+    });
+}
+
+async fn handle_health(State(status): State<SharedStatus>) -> Json<BotStatus> {
+    Json(status.read().await.clone())
+}
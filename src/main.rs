@@ -1,13 +1,25 @@
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 use serenity::{all::CreateEmbed, builder::ExecuteWebhook, http::Http, model::webhook::Webhook};
 use tracing::{info, error, warn};
-use twitch_api::{helix::streams::get_streams, twitch_oauth2::AppAccessToken, types, HelixClient};
+use twitch_api::{
+    helix::streams::get_streams,
+    twitch_oauth2::{AppAccessToken, TwitchToken},
+    types, HelixClient,
+};
 use miette::Result;
 use kvstore_client::{KvStoreClient, generated::{GetRequest, SetRequest}};
 use tonic::transport::Channel;
 
 mod error;
+mod eventsub;
+mod health;
+mod template;
+mod twitch_irc;
 use error::GdqBotError;
+use health::SharedStatus;
+use template::{MessageContext, MessageTemplate};
+use twitch_irc::TwitchIrcHandle;
 
 // Constants
 const DEFAULT_TWITCH_CHANNEL_NAME: &str = "gamesdonequick";
@@ -17,6 +29,13 @@ const POLL_RATE: Duration = Duration::from_secs(2 * 60);
 const USERNAME: &str = "GDQBot";
 const TWITCH_BASE_URL: &str = "https://www.twitch.tv/";
 const DEFAULT_OFFLINE_THRESHOLD: u32 = 3;
+const DEFAULT_EVENTSUB_PORT: u16 = 8080;
+const DEFAULT_HEALTH_PORT: u16 = 8081;
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+const INITIAL_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(2 * 60);
+/// Helix batches up to 100 `user_login` values into a single `GetStreams` call.
+const HELIX_MAX_LOGINS_PER_REQUEST: usize = 100;
 
 #[tokio::main]
 async fn main() -> Result<(), GdqBotError> {
@@ -27,15 +46,21 @@ async fn main() -> Result<(), GdqBotError> {
     let mut bot = GdqBot::new();
     bot.init_helix().await?;
     bot.init_kvstore().await?;
-    match bot.get_current_game_from_db().await {
-        Ok(game) => info!("Current game from DB: {}", game),
-        Err(e) => warn!("Failed to get current game from DB, starting fresh: {}", e),
-    };
+    bot.init_irc();
+    for channel in bot.channel_names.clone() {
+        match bot.get_current_game_from_db(&channel).await {
+            Ok(game) => info!("{}: current game from DB: {}", channel, game),
+            Err(e) => warn!("{}: failed to get current game from DB, starting fresh: {}", channel, e),
+        };
+    }
     info!(
-        "Starting bot with offline threshold of {} checks",
+        "Starting bot for {} channel(s) with offline threshold of {} checks",
+        bot.channel_names.len(),
         bot.offline_threshold
     );
 
+    health::spawn_server(bot.health_port, bot.status.clone());
+
     match bot.run().await {
         Ok(()) => {
             info!("Bot finished normally");
@@ -57,29 +82,58 @@ async fn main() -> Result<(), GdqBotError> {
 }
 
 struct GdqBot<'a> {
-    channel_name: String,
+    channel_names: Vec<String>,
     client_id: twitch_api::twitch_oauth2::ClientId,
     client_secret: twitch_api::twitch_oauth2::ClientSecret,
     access_token: Option<AppAccessToken>,
-    current_game: String,
+    current_games: HashMap<String, String>,
     kvstore_url: String,
     kvstore_token: String,
     kvstore_client: Option<KvStoreClient<Channel>>,
     helix_client: HelixClient<'a, reqwest::Client>,
     webhooks: Vec<String>,
-    offline_count: u32,
+    offline_counts: HashMap<String, u32>,
     offline_threshold: u32,
+    use_eventsub: bool,
+    eventsub_port: u16,
+    eventsub_secret: String,
+    eventsub_callback_url: String,
+    message_template: MessageTemplate,
+    irc_nick: String,
+    irc_token: String,
+    irc_client: Option<TwitchIrcHandle>,
+    health_port: u16,
+    status: SharedStatus,
 }
 
 trait GdqBotTrait {
     fn new() -> Self;
     async fn init_helix(&mut self) -> Result<(), GdqBotError>;
     async fn init_kvstore(&mut self) -> Result<(), GdqBotError>;
+    fn init_irc(&mut self);
     async fn run(&mut self) -> Result<(), GdqBotError>;
-    async fn get_current_game_from_db(&mut self) -> Result<String, GdqBotError>;
-    async fn set_current_game_to_db(&mut self, game: &str) -> Result<(), error::GdqBotError>;
-    async fn send_game_change_message(&self, game: &str, stream_title: &str) -> Result<(), error::GdqBotError>;
-    async fn get_current_game_from_twitch(&mut self) -> Result<Option<String>, GdqBotError>;
+    async fn poll_and_check_offline(&mut self) -> Result<(), GdqBotError>;
+    async fn ensure_fresh_token(&mut self) -> Result<(), GdqBotError>;
+    async fn reconnect(&mut self) -> Result<(), GdqBotError>;
+    async fn get_current_game_from_db(&mut self, channel: &str) -> Result<String, GdqBotError>;
+    async fn set_current_game_to_db(&mut self, channel: &str, game: &str) -> Result<(), error::GdqBotError>;
+    async fn send_game_change_message(&self, channel: &str, game: &str, previous_game: &str, stream_title: &str, viewer_count: i64) -> Result<(), error::GdqBotError>;
+    async fn get_current_game_from_twitch(&mut self) -> Result<(), GdqBotError>;
+    async fn handle_game_update(&mut self, channel: &str, game: &str, stream_title: &str, viewer_count: i64) -> Result<(), GdqBotError>;
+    async fn handle_eventsub_message(&mut self, message: eventsub::EventSubMessage) -> Result<(), GdqBotError>;
+}
+
+/// Resolves a Helix-returned login (always lowercase) back to the
+/// configured-case entry in `channel_names`, so state keyed by the
+/// configured casing (KVStore, `current_games`, `offline_counts`) doesn't
+/// split across two different keys for the same channel.
+fn resolve_channel<'b>(channel_names: &'b [String], login: &str) -> Option<&'b String> {
+    channel_names.iter().find(|c| c.eq_ignore_ascii_case(login))
+}
+
+/// Builds the per-channel KVStore key, e.g. `gdq_game:gamesdonequick`.
+fn kvstore_key(channel: &str) -> String {
+    format!("{}:{}", KVSTORE_KEY, channel)
 }
 
 /// Represents a GDQBot instance.
@@ -91,20 +145,45 @@ impl<'a> GdqBotTrait for GdqBot<'a> {
             .ok()
             .and_then(|s| s.parse().ok())
             .unwrap_or(DEFAULT_OFFLINE_THRESHOLD);
+        let channel_names: Vec<String> = std::env::var("TWITCH_CHANNEL_NAME")
+            .unwrap_or(DEFAULT_TWITCH_CHANNEL_NAME.to_string())
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
 
         GdqBot {
-            channel_name: std::env::var("TWITCH_CHANNEL_NAME").unwrap_or(DEFAULT_TWITCH_CHANNEL_NAME.to_string()),
+            channel_names,
             client_id: twitch_api::twitch_oauth2::ClientId::new(std::env::var("TWITCH_CLIENT_ID").unwrap_or("".to_string())),
             client_secret: twitch_api::twitch_oauth2::ClientSecret::new(std::env::var("TWITCH_CLIENT_SECRET").unwrap_or("".to_string())),
             access_token: None,
-            current_game: "".to_string(),
+            current_games: HashMap::new(),
             kvstore_url: std::env::var("KVSTORE_URL").unwrap_or(DEFAULT_KVSTORE_URL.to_string()),
             kvstore_token: std::env::var("KVSTORE_TOKEN").unwrap_or("".to_string()),
             kvstore_client: None,
             helix_client: HelixClient::default(),
             webhooks: vec![webhook_url],
-            offline_count: 0,
+            offline_counts: HashMap::new(),
             offline_threshold,
+            use_eventsub: std::env::var("USE_EVENTSUB")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+            eventsub_port: std::env::var("EVENTSUB_PORT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(DEFAULT_EVENTSUB_PORT),
+            eventsub_secret: std::env::var("EVENTSUB_SECRET").unwrap_or("".to_string()),
+            eventsub_callback_url: std::env::var("EVENTSUB_CALLBACK_URL").unwrap_or("".to_string()),
+            message_template: MessageTemplate::load(),
+            irc_nick: std::env::var("TWITCH_IRC_NICK").unwrap_or(USERNAME.to_lowercase()),
+            irc_token: std::env::var("TWITCH_IRC_TOKEN").unwrap_or("".to_string()),
+            irc_client: None,
+            health_port: std::env::var("HEALTH_PORT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(DEFAULT_HEALTH_PORT),
+            status: health::BotStatus::shared(offline_threshold),
         }
     }
 
@@ -113,6 +192,7 @@ impl<'a> GdqBotTrait for GdqBot<'a> {
         let client = kvstore_client::connect(&self.kvstore_url).await?;
         info!("Connected to KVStore at {}", self.kvstore_url);
         self.kvstore_client = Some(client);
+        self.status.write().await.kvstore_connected = true;
         Ok(())
     }
 
@@ -137,49 +217,154 @@ impl<'a> GdqBotTrait for GdqBot<'a> {
             Ok(token) => {
                 info!("App access token retrieved successfully");
                 self.access_token = Some(token);
+                self.status.write().await.helix_connected = true;
                 Ok(())
             }
         }
     }
 
+    /// Connects to Twitch chat over IRC and joins every monitored channel,
+    /// if `TWITCH_IRC_TOKEN` is configured. Reconnection is handled entirely
+    /// by the background task spawned in [`TwitchIrcHandle::connect`].
+    fn init_irc(&mut self) {
+        if self.irc_token.is_empty() {
+            return;
+        }
+
+        self.irc_client = Some(TwitchIrcHandle::connect(
+            self.irc_nick.clone(),
+            self.irc_token.clone(),
+            self.channel_names.clone(),
+        ));
+    }
+
     /// Starts the bot and continuously checks for game changes.
     /// Exits gracefully after consecutive offline checks exceed threshold.
+    ///
+    /// When `use_eventsub` is set and at least one EventSub subscription was
+    /// actually created, game changes are driven by pushed `channel.update`
+    /// notifications instead of by polling on every tick. Otherwise — no
+    /// callback URL configured, or subscription creation failed outright —
+    /// the interval falls back to polling so offline detection and game
+    /// changes still work.
+    ///
+    /// Transient `HelixError`/`HelixAccessError`/`TonicStatus`/
+    /// `TonicTransportError` failures trigger an exponential-backoff
+    /// reconnect instead of ending the process; only `StreamOffline`/
+    /// `StreamRerun` still exit the loop.
     async fn run(&mut self) -> Result<(), GdqBotError> {
         let mut interval = tokio::time::interval(POLL_RATE);
+        let mut eventsub_active = false;
+        let mut eventsub_rx = if self.use_eventsub {
+            info!("EventSub enabled, listening on port {}", self.eventsub_port);
+            let rx = eventsub::spawn_listener(self.eventsub_port, self.eventsub_secret.clone());
+
+            if self.eventsub_callback_url.is_empty() {
+                warn!("EVENTSUB_CALLBACK_URL not set, skipping subscription creation");
+            } else {
+                match eventsub::create_subscriptions(
+                    &self.helix_client,
+                    self.access_token.as_ref().unwrap(),
+                    &self.channel_names,
+                    &self.eventsub_callback_url,
+                    &self.eventsub_secret,
+                ).await {
+                    Ok(created) => eventsub_active = created,
+                    Err(e) => warn!("Failed to create EventSub subscriptions: {}", e),
+                }
+            }
+
+            if !eventsub_active {
+                warn!("No EventSub subscriptions active, falling back to polling");
+            }
+
+            Some(rx)
+        } else {
+            None
+        };
+        let mut backoff = INITIAL_RECONNECT_BACKOFF;
+
         loop {
-            interval.tick().await; // This should go first.
-            match self.get_current_game_from_twitch().await? {
-                Some(_) => {
-                    // Stream is online, reset offline counter
-                    self.offline_count = 0;
+            let result = tokio::select! {
+                _ = interval.tick() => {
+                    if eventsub_active {
+                        // EventSub drives game changes and offline detection; the tick does not poll.
+                        Ok(())
+                    } else {
+                        self.poll_and_check_offline().await
+                    }
                 }
-                None => {
-                    // Stream is offline
-                    self.offline_count += 1;
-                    info!(
-                        "Stream offline check {}/{}",
-                        self.offline_count, self.offline_threshold
-                    );
-
-                    if self.offline_count >= self.offline_threshold {
-                        info!(
-                            "Stream has been offline for {} consecutive checks. Exiting gracefully.",
-                            self.offline_count
-                        );
-                        return Err(GdqBotError::StreamOffline(self.offline_count));
+                Some(event) = async {
+                    match eventsub_rx.as_mut() {
+                        Some(rx) => rx.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => self.handle_eventsub_message(event).await,
+            };
+
+            match result {
+                Ok(()) => backoff = INITIAL_RECONNECT_BACKOFF,
+                Err(e @ (GdqBotError::StreamOffline(_) | GdqBotError::StreamRerun(_))) => return Err(e),
+                Err(e @ (GdqBotError::HelixError(_) | GdqBotError::HelixAccessError(_) | GdqBotError::TonicStatus(_) | GdqBotError::TonicTransportError(_))) => {
+                    warn!("Transient error, reconnecting in {:?}: {}", backoff, e);
+                    {
+                        let mut status = self.status.write().await;
+                        status.kvstore_connected = false;
+                        status.helix_connected = false;
+                    }
+                    tokio::time::sleep(backoff).await;
+                    if let Err(reconnect_err) = self.reconnect().await {
+                        error!("Reconnect attempt failed: {}", reconnect_err);
                     }
+                    backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
                 }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Re-runs client initialization after a transient connectivity failure.
+    async fn reconnect(&mut self) -> Result<(), GdqBotError> {
+        self.init_kvstore().await?;
+        self.init_helix().await?;
+        Ok(())
+    }
+
+    /// Refreshes the app access token if it is within [`TOKEN_REFRESH_MARGIN`]
+    /// of expiring, so long-running marathons don't die on an expired token.
+    async fn ensure_fresh_token(&mut self) -> Result<(), GdqBotError> {
+        let needs_refresh = match &self.access_token {
+            Some(token) => token.expires_in() < TOKEN_REFRESH_MARGIN,
+            None => true,
+        };
+
+        if !needs_refresh {
+            return Ok(());
+        }
+
+        info!("App access token near expiry, refreshing");
+        if let Some(token) = self.access_token.as_mut() {
+            if token.refresh_token(&self.helix_client).await.is_ok() {
+                return Ok(());
             }
+            warn!("Refresh token failed, re-fetching a new app access token");
         }
+
+        self.init_helix().await
     }
 
-    /// Retrieves the current game from the key-value store.
-    async fn get_current_game_from_db(&mut self) -> Result<String, GdqBotError> {
+    /// Polls Twitch once and updates the offline bookkeeping accordingly.
+    async fn poll_and_check_offline(&mut self) -> Result<(), GdqBotError> {
+        self.get_current_game_from_twitch().await
+    }
+
+    /// Retrieves the current game for `channel` from the key-value store.
+    async fn get_current_game_from_db(&mut self, channel: &str) -> Result<String, GdqBotError> {
         let client = self.kvstore_client.as_mut()
             .ok_or_else(|| GdqBotError::Other("KVStore client not initialized".to_string()))?;
 
         let request = GetRequest {
-            key: KVSTORE_KEY.to_string(),
+            key: kvstore_key(channel),
             token: self.kvstore_token.clone(),
         };
 
@@ -189,93 +374,228 @@ impl<'a> GdqBotTrait for GdqBot<'a> {
             return Err(GdqBotError::Other("Game not found in KVStore".to_string()));
         }
 
-        self.current_game = response.value;
-        Ok(self.current_game.clone())
+        self.current_games.insert(channel.to_string(), response.value.clone());
+        Ok(response.value)
     }
 
-    /// Sets the current game in the key-value store.
+    /// Sets the current game for `channel` in the key-value store.
     ///
     /// # Errors
     ///
     /// Returns an error if the game cannot be set in the key-value store.
-    async fn set_current_game_to_db(&mut self, game: &str) -> Result<(), error::GdqBotError> {
+    async fn set_current_game_to_db(&mut self, channel: &str, game: &str) -> Result<(), error::GdqBotError> {
         let client = self.kvstore_client.as_mut()
             .ok_or_else(|| GdqBotError::Other("KVStore client not initialized".to_string()))?;
 
         let request = SetRequest {
-            key: KVSTORE_KEY.to_string(),
+            key: kvstore_key(channel),
             value: game.to_string(),
             token: self.kvstore_token.clone(),
             ttl_seconds: None,
         };
 
         client.set(request).await?;
-        info!("Saved game to KVStore: {}", game);
+        info!("{}: saved game to KVStore: {}", channel, game);
 
         Ok(())
     }
 
-    /// Sends a game change message through webhooks.
-    /// 
+    /// Sends a game change message through webhooks, rendered by the
+    /// configured [`MessageTemplate`]. The template may suppress the
+    /// announcement entirely (e.g. for a switch to "Just Chatting"), in which
+    /// case no webhook is called.
+    ///
     /// # Errors
-    /// 
+    ///
     /// Returns an error if the game change message cannot be sent.
-    async fn send_game_change_message(&self, game: &str, stream_title: &str) -> Result<(), error::GdqBotError> {
+    async fn send_game_change_message(&self, channel: &str, game: &str, previous_game: &str, stream_title: &str, viewer_count: i64) -> Result<(), error::GdqBotError> {
+        let ctx = MessageContext {
+            game,
+            previous_game,
+            stream_title,
+            channel,
+            url: &format!("{}{}", TWITCH_BASE_URL, channel),
+            viewer_count,
+        };
+
+        let Some(rendered) = self.message_template.render(&ctx) else {
+            info!("{}: template suppressed announcement for game: {}", channel, game);
+            return Ok(());
+        };
+
         for webhook in self.webhooks.iter() {
             let http = Http::new("");
             let webhook = Webhook::from_url(&http, webhook).await?;
             let embed = CreateEmbed::new()
-                .title("GDQ hype!")
-                .description(format!("Game changed to **{}**\n*{}*\n{}{}", &game, &stream_title, &TWITCH_BASE_URL, &self.channel_name));
+                .title(rendered.title.clone())
+                .description(rendered.description.clone());
             let builder = ExecuteWebhook::new().embed(embed).username(USERNAME);
             webhook.execute(&http, false, builder).await?;
         }
 
-        info!("Game changed to: {}", game);
-    
+        info!("{}: game changed to: {}", channel, game);
+
         Ok(())
     }
 
-    /// Retrieves the current game from Twitch API.
-    /// 
+    /// Retrieves the current game for every monitored channel from Twitch API
+    /// in a single batched `GetStreams` call (paginating if Helix splits the
+    /// response), diffing each channel's game against its own stored value.
+    ///
+    /// Offline bookkeeping is per-channel: a channel that is offline for
+    /// `offline_threshold` consecutive checks, or whose title marks it as a
+    /// rerun, is dropped from monitoring rather than ending the whole
+    /// process, unless it was the last channel left.
+    ///
     /// # Errors
-    /// 
-    /// Returns an error if the current game cannot be retrieved from Twitch API.
-    async fn get_current_game_from_twitch(&mut self) -> Result<Option<String>, GdqBotError> {
-        let logins: &[&types::UserNameRef] = &[self.channel_name.as_str().into()];
-        let request = get_streams::GetStreamsRequest::builder()
-            .user_login(logins)
-            .build();
-        let response: Vec<get_streams::Stream> = self.helix_client.req_get(request, &self.access_token.clone().unwrap()).await?.data;
-
-        if response.is_empty() {
-            warn!("Stream is offline");
-            return Ok(None);
+    ///
+    /// Returns an error if the streams cannot be retrieved from Twitch API, or
+    /// `StreamOffline`/`StreamRerun` (whichever dropped the last channel) once
+    /// every monitored channel has been dropped.
+    async fn get_current_game_from_twitch(&mut self) -> Result<(), GdqBotError> {
+        self.ensure_fresh_token().await?;
+
+        let mut streams: Vec<get_streams::Stream> = Vec::new();
+        for chunk in self.channel_names.clone().chunks(HELIX_MAX_LOGINS_PER_REQUEST) {
+            let logins: Vec<&types::UserNameRef> = chunk.iter().map(|c| c.as_str().into()).collect();
+            let mut after: Option<String> = None;
+            loop {
+                let mut builder = get_streams::GetStreamsRequest::builder().user_login(logins.clone());
+                if let Some(cursor) = &after {
+                    builder = builder.after(cursor.clone().into());
+                }
+                let request = builder.build();
+                let response = self.helix_client.req_get(request, &self.access_token.clone().unwrap()).await?;
+                streams.extend(response.data);
+
+                after = response.pagination.and_then(|p| p.cursor).map(|c| c.to_string());
+                if after.is_none() {
+                    break;
+                }
+            }
+        }
+
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut drop_reason: Option<GdqBotError> = None;
+        for stream in &streams {
+            // Helix always returns `user_login` lowercased; resolve it back to
+            // the configured-case entry so KVStore keys and in-memory state
+            // stay on one key per channel.
+            let Some(channel) = resolve_channel(&self.channel_names, stream.user_login.as_str()).cloned() else {
+                continue;
+            };
+            seen.insert(channel.clone());
+
+            let game = stream.game_name.to_string();
+            let stream_title = stream.title.to_string();
+            let viewer_count = stream.viewer_count as i64;
+
+            if stream_title.to_lowercase().contains("rerun") {
+                let err = GdqBotError::StreamRerun(stream_title.clone());
+                warn!("{}: {}", channel, err);
+                self.channel_names.retain(|c| c != &channel);
+                self.offline_counts.remove(&channel);
+                self.status.write().await.drop_channel(&channel);
+                drop_reason = Some(err);
+                continue;
+            }
+
+            info!("{}: got current game from Twitch: {}", channel, game);
+            self.offline_counts.insert(channel.clone(), 0);
+            self.handle_game_update(&channel, &game, &stream_title, viewer_count).await?;
+        }
+
+        for channel in self.channel_names.clone() {
+            if seen.contains(&channel) {
+                continue;
+            }
+
+            let count_ref = self.offline_counts.entry(channel.clone()).or_insert(0);
+            *count_ref += 1;
+            let count = *count_ref;
+            info!("{}: offline check {}/{}", channel, count, self.offline_threshold);
+            self.status.write().await.record_offline(&channel, count);
+
+            if count >= self.offline_threshold {
+                info!("{}: offline for {} consecutive checks, dropping from monitoring", channel, count);
+                self.channel_names.retain(|c| c != &channel);
+                self.offline_counts.remove(&channel);
+                self.status.write().await.drop_channel(&channel);
+                drop_reason = Some(GdqBotError::StreamOffline(count));
+            }
         }
-        let game = String::from(response.first().unwrap().game_name.as_str());
-        let stream_title: String = String::from(response.first().unwrap().title.as_str());
 
-        // Check if stream is a rerun
-        if stream_title.to_lowercase().contains("rerun") {
-            info!("Stream is a rerun: {}", stream_title);
-            return Err(GdqBotError::StreamRerun(stream_title));
+        if self.channel_names.is_empty() {
+            return Err(drop_reason.unwrap_or(GdqBotError::StreamOffline(self.offline_threshold)));
         }
 
-        info!("Got current game from Twitch: {}", game);
+        Ok(())
+    }
+
+    /// Reacts to a verified EventSub notification. `channel.update` drives
+    /// the same persist-and-notify path as polling; `stream.online` resets
+    /// offline bookkeeping; `stream.offline` drops the channel from
+    /// monitoring immediately instead of waiting out `offline_threshold`.
+    async fn handle_eventsub_message(&mut self, message: eventsub::EventSubMessage) -> Result<(), GdqBotError> {
+        match message {
+            eventsub::EventSubMessage::ChannelUpdate(event) => {
+                let Some(channel) = resolve_channel(&self.channel_names, &event.broadcaster_login).cloned() else {
+                    return Ok(());
+                };
+                self.offline_counts.insert(channel.clone(), 0);
+                self.handle_game_update(&channel, &event.category_name, &event.stream_title, 0).await
+            }
+            eventsub::EventSubMessage::StreamOnline { broadcaster_login } => {
+                let Some(channel) = resolve_channel(&self.channel_names, &broadcaster_login).cloned() else {
+                    return Ok(());
+                };
+                self.offline_counts.insert(channel.clone(), 0);
+                let game = self.current_games.get(&channel).cloned().unwrap_or_default();
+                self.status.write().await.mark_online(&channel, &game);
+                Ok(())
+            }
+            eventsub::EventSubMessage::StreamOffline { broadcaster_login } => {
+                let Some(channel) = resolve_channel(&self.channel_names, &broadcaster_login).cloned() else {
+                    return Ok(());
+                };
+                info!("{}: EventSub reported stream offline, dropping from monitoring", channel);
+                self.channel_names.retain(|c| c != &channel);
+                self.offline_counts.remove(&channel);
+                self.status.write().await.drop_channel(&channel);
+
+                if self.channel_names.is_empty() {
+                    return Err(GdqBotError::StreamOffline(self.offline_threshold));
+                }
+                Ok(())
+            }
+        }
+    }
 
-        // Game name changed, save it to db and send message through webhook
-        if game.ne(&self.current_game) {
-            if let Err(e) = self.set_current_game_to_db(&game).await {
-                error!("Failed to save game to KVStore: {}", e);
+    /// Applies a freshly observed game/title for `channel`, persisting and
+    /// notifying only if it differs from what we already have on record.
+    ///
+    /// Shared by the polling path and the EventSub `channel.update` path so
+    /// both drive the same persist-and-notify behavior.
+    async fn handle_game_update(&mut self, channel: &str, game: &str, stream_title: &str, viewer_count: i64) -> Result<(), GdqBotError> {
+        let previous = self.current_games.get(channel).cloned().unwrap_or_default();
+        if game.ne(&previous) {
+            if let Err(e) = self.set_current_game_to_db(channel, game).await {
+                error!("{}: failed to save game to KVStore: {}", channel, e);
+            }
+            if let Err(e) = self.send_game_change_message(channel, game, &previous, stream_title, viewer_count).await {
+                error!("{}: failed to send game change message: {}", channel, e);
             }
-            if let Err(e) = self.send_game_change_message(&game, &stream_title).await {
-                error!("Failed to send game change message: {}", e);
+            if let Some(irc) = &self.irc_client {
+                irc.send_message(channel, &format!("Game changed to {}", game));
             }
+            self.status.write().await.record_game_change(channel, game);
+        } else {
+            self.status.write().await.mark_online(channel, game);
         }
 
-        self.current_game = game;
+        self.current_games.insert(channel.to_string(), game.to_string());
 
-        Ok(Some(self.current_game.clone()))
+        Ok(())
     }
 }
 
@@ -292,10 +612,10 @@ mod tests {
         let bot = GdqBot::new();
 
         // Test defaults that don't depend on env
-        assert_eq!(bot.channel_name, "gamesdonequick");
+        assert_eq!(bot.channel_names, vec!["gamesdonequick".to_string()]);
         assert!(bot.access_token.is_none());
-        assert_eq!(bot.current_game, "");
-        assert_eq!(bot.offline_count, 0);
+        assert!(bot.current_games.is_empty());
+        assert!(bot.offline_counts.is_empty());
         assert_eq!(bot.offline_threshold, DEFAULT_OFFLINE_THRESHOLD);
 
         // Test custom offline threshold (in same test to avoid race condition)
@@ -303,5 +623,11 @@ mod tests {
         let bot2 = GdqBot::new();
         assert_eq!(bot2.offline_threshold, 5);
         std::env::remove_var("OFFLINE_CHECK_COUNT");
+
+        // Test multi-channel parsing (in same test to avoid race condition)
+        std::env::set_var("TWITCH_CHANNEL_NAME", "gamesdonequick, some_other_runner ,");
+        let bot3 = GdqBot::new();
+        assert_eq!(bot3.channel_names, vec!["gamesdonequick".to_string(), "some_other_runner".to_string()]);
+        std::env::remove_var("TWITCH_CHANNEL_NAME");
     }
 }
@@ -0,0 +1,159 @@
+use rhai::{Dynamic, Engine, Scope, AST};
+use tracing::{error, warn};
+
+const DEFAULT_TITLE: &str = "GDQ hype!";
+
+/// Everything a template or script might want to reference when rendering a
+/// game-change announcement.
+pub struct MessageContext<'a> {
+    pub game: &'a str,
+    pub previous_game: &'a str,
+    pub stream_title: &'a str,
+    pub channel: &'a str,
+    pub url: &'a str,
+    pub viewer_count: i64,
+}
+
+/// A rendered announcement, or `None` if the template chose to suppress it
+/// (e.g. a switch to "Just Chatting").
+pub struct RenderedMessage {
+    pub title: String,
+    pub description: String,
+}
+
+/// Renders game-change announcements, either via a user-supplied Rhai script
+/// or a simple `{placeholder}` template string. Falls back to the bot's
+/// historical hardcoded embed when neither is configured.
+pub enum MessageTemplate {
+    Script { engine: Engine, ast: AST },
+    Placeholder(String),
+    Default,
+}
+
+impl MessageTemplate {
+    /// Loads a template from, in priority order: `TEMPLATE_SCRIPT_PATH` (a
+    /// Rhai script file), `TEMPLATE_SCRIPT` (inline Rhai source),
+    /// `MESSAGE_TEMPLATE` (a `{game}`/`{title}`/`{channel}`/`{url}` template
+    /// string), or the bot's hardcoded default.
+    pub fn load() -> Self {
+        if let Ok(path) = std::env::var("TEMPLATE_SCRIPT_PATH") {
+            return match std::fs::read_to_string(&path) {
+                Ok(source) => Self::compile_script(&source),
+                Err(e) => {
+                    error!("Failed to read TEMPLATE_SCRIPT_PATH {}: {}", path, e);
+                    Self::Default
+                }
+            };
+        }
+
+        if let Ok(source) = std::env::var("TEMPLATE_SCRIPT") {
+            return Self::compile_script(&source);
+        }
+
+        if let Ok(template) = std::env::var("MESSAGE_TEMPLATE") {
+            return Self::Placeholder(template);
+        }
+
+        Self::Default
+    }
+
+    fn compile_script(source: &str) -> Self {
+        let engine = Engine::new();
+        match engine.compile(source) {
+            Ok(ast) => Self::Script { engine, ast },
+            Err(e) => {
+                error!("Failed to compile template script: {}", e);
+                Self::Default
+            }
+        }
+    }
+
+    /// Renders the announcement for `ctx`, or `None` if it should be
+    /// suppressed entirely.
+    pub fn render(&self, ctx: &MessageContext) -> Option<RenderedMessage> {
+        match self {
+            Self::Script { engine, ast } => Self::render_script(engine, ast, ctx),
+            Self::Placeholder(template) => Some(RenderedMessage {
+                title: DEFAULT_TITLE.to_string(),
+                description: Self::apply_placeholders(template, ctx),
+            }),
+            Self::Default => Some(RenderedMessage {
+                title: DEFAULT_TITLE.to_string(),
+                description: format!(
+                    "Game changed to **{}**\n*{}*\n{}",
+                    ctx.game, ctx.stream_title, ctx.url
+                ),
+            }),
+        }
+    }
+
+    fn render_script(engine: &Engine, ast: &AST, ctx: &MessageContext) -> Option<RenderedMessage> {
+        let mut scope = Scope::new();
+        scope.push("game", ctx.game.to_string());
+        scope.push("previous_game", ctx.previous_game.to_string());
+        scope.push("title", ctx.stream_title.to_string());
+        scope.push("channel", ctx.channel.to_string());
+        scope.push("url", ctx.url.to_string());
+        scope.push("viewer_count", ctx.viewer_count);
+
+        let result: Result<Dynamic, _> = engine.eval_ast_with_scope(&mut scope, ast);
+        match result {
+            Ok(value) if value.is_unit() => None,
+            Ok(value) => {
+                let map = value.try_cast::<rhai::Map>()?;
+                let title = map.get("title").map(|v| v.to_string()).unwrap_or(DEFAULT_TITLE.to_string());
+                let description = map.get("description").map(|v| v.to_string()).unwrap_or_default();
+                Some(RenderedMessage { title, description })
+            }
+            Err(e) => {
+                warn!("Template script failed, suppressing message: {}", e);
+                None
+            }
+        }
+    }
+
+    fn apply_placeholders(template: &str, ctx: &MessageContext) -> String {
+        template
+            .replace("{game}", ctx.game)
+            .replace("{title}", ctx.stream_title)
+            .replace("{channel}", ctx.channel)
+            .replace("{url}", ctx.url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholder_template_substitutes_all_fields() {
+        let template = MessageTemplate::Placeholder("{channel} is now playing {game} ({title}) - {url}".to_string());
+        let ctx = MessageContext {
+            game: "Celeste",
+            previous_game: "Just Chatting",
+            stream_title: "GDQ Marathon",
+            channel: "gamesdonequick",
+            url: "https://www.twitch.tv/gamesdonequick",
+            viewer_count: 1234,
+        };
+
+        let rendered = template.render(&ctx).expect("template should render");
+        assert_eq!(rendered.description, "gamesdonequick is now playing Celeste (GDQ Marathon) - https://www.twitch.tv/gamesdonequick");
+    }
+
+    #[test]
+    fn test_default_template_matches_historical_format() {
+        let ctx = MessageContext {
+            game: "Celeste",
+            previous_game: "",
+            stream_title: "GDQ Marathon",
+            channel: "gamesdonequick",
+            url: "https://www.twitch.tv/gamesdonequick",
+            viewer_count: 0,
+        };
+
+        let rendered = MessageTemplate::Default.render(&ctx).expect("default should always render");
+        assert_eq!(rendered.title, DEFAULT_TITLE);
+        assert_eq!(rendered.description, "Game changed to **Celeste**\n*GDQ Marathon*\nhttps://www.twitch.tv/gamesdonequick");
+    }
+}
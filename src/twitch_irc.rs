@@ -0,0 +1,142 @@
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_native_tls::{native_tls, TlsConnector, TlsStream};
+use tracing::{info, warn};
+
+use crate::error::GdqBotError;
+
+const IRC_HOST: &str = "irc.chat.twitch.tv";
+const IRC_PORT: u16 = 6697;
+const INITIAL_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(60);
+
+enum IrcCommand {
+    Privmsg { channel: String, message: String },
+}
+
+/// A handle to a background Twitch IRC connection. Cheap to clone and safe
+/// to keep around even while the connection is mid-reconnect; messages sent
+/// during a gap stay queued and are sent once the connection is back.
+#[derive(Clone)]
+pub struct TwitchIrcHandle {
+    tx: mpsc::UnboundedSender<IrcCommand>,
+}
+
+impl TwitchIrcHandle {
+    /// Spawns the background connection task, which joins `channels` and
+    /// reconnects with exponential backoff whenever the socket drops.
+    pub fn connect(nick: String, token: String, channels: Vec<String>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run_connection_loop(nick, token, channels, rx));
+        Self { tx }
+    }
+
+    /// Queues a PRIVMSG to `channel`. Fire-and-forget: delivery isn't
+    /// guaranteed if the connection is currently down.
+    pub fn send_message(&self, channel: &str, message: &str) {
+        let command = IrcCommand::Privmsg {
+            channel: channel.to_string(),
+            message: message.to_string(),
+        };
+        if self.tx.send(command).is_err() {
+            warn!("Twitch IRC task is gone, dropping message for {}", channel);
+        }
+    }
+}
+
+async fn run_connection_loop(
+    nick: String,
+    token: String,
+    channels: Vec<String>,
+    mut rx: mpsc::UnboundedReceiver<IrcCommand>,
+) {
+    let mut backoff = INITIAL_RECONNECT_BACKOFF;
+
+    loop {
+        match connect_and_join(&nick, &token, &channels).await {
+            Ok(stream) => {
+                info!("Connected to Twitch IRC as {}", nick);
+                backoff = INITIAL_RECONNECT_BACKOFF;
+                if let Err(e) = serve(stream, &mut rx).await {
+                    warn!("Twitch IRC connection lost: {}", e);
+                }
+            }
+            Err(e) => warn!("Failed to connect to Twitch IRC: {}", e),
+        }
+
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+    }
+}
+
+async fn connect_and_join(
+    nick: &str,
+    token: &str,
+    channels: &[String],
+) -> Result<TlsStream<TcpStream>, GdqBotError> {
+    let tcp = TcpStream::connect((IRC_HOST, IRC_PORT))
+        .await
+        .map_err(|e| GdqBotError::IrcError(format!("tcp connect failed: {}", e)))?;
+    let connector = TlsConnector::from(
+        native_tls::TlsConnector::new()
+            .map_err(|e| GdqBotError::IrcError(format!("tls setup failed: {}", e)))?,
+    );
+    let mut stream = connector
+        .connect(IRC_HOST, tcp)
+        .await
+        .map_err(|e| GdqBotError::IrcError(format!("tls handshake failed: {}", e)))?;
+
+    write_line(&mut stream, &format!("PASS oauth:{}", token)).await?;
+    write_line(&mut stream, &format!("NICK {}", nick)).await?;
+    for channel in channels {
+        write_line(&mut stream, &format!("JOIN #{}", channel)).await?;
+    }
+
+    Ok(stream)
+}
+
+async fn serve(
+    stream: TlsStream<TcpStream>,
+    rx: &mut mpsc::UnboundedReceiver<IrcCommand>,
+) -> Result<(), GdqBotError> {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut lines = BufReader::new(reader).lines();
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line.map_err(|e| GdqBotError::IrcError(format!("read failed: {}", e)))? else {
+                    return Err(GdqBotError::IrcError("connection closed by server".to_string()));
+                };
+                if let Some(server) = line.strip_prefix("PING ") {
+                    write_line_raw(&mut writer, &format!("PONG {}", server)).await?;
+                }
+            }
+            command = rx.recv() => {
+                let Some(IrcCommand::Privmsg { channel, message }) = command else {
+                    return Err(GdqBotError::IrcError("command channel closed".to_string()));
+                };
+                write_line_raw(&mut writer, &format!("PRIVMSG #{} :{}", channel, message)).await?;
+            }
+        }
+    }
+}
+
+async fn write_line(stream: &mut TlsStream<TcpStream>, line: &str) -> Result<(), GdqBotError> {
+    stream
+        .write_all(format!("{}\r\n", line).as_bytes())
+        .await
+        .map_err(|e| GdqBotError::IrcError(format!("write failed: {}", e)))
+}
+
+async fn write_line_raw<W: tokio::io::AsyncWrite + Unpin>(
+    writer: &mut W,
+    line: &str,
+) -> Result<(), GdqBotError> {
+    writer
+        .write_all(format!("{}\r\n", line).as_bytes())
+        .await
+        .map_err(|e| GdqBotError::IrcError(format!("write failed: {}", e)))
+}